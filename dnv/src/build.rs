@@ -0,0 +1,132 @@
+//! Concurrent `docker buildx` execution over a distro x arch matrix.
+//!
+//! Until now `dnv` only produced Dockerfile text -- `Profile.arch` was
+//! stored but nothing ever read it. `run_matrix` expands each profile's
+//! (possibly comma-separated) `arch` list into one `BuildTarget` per
+//! distro/arch pair and drives `docker buildx build --platform linux/$arch`
+//! for each, the way the netdata Dagger pipeline fans a handful of distros
+//! out across several platforms at once. A bounded pool of worker threads
+//! pulls targets off a shared queue so only `max_concurrency` builds run at
+//! a time, and each build reuses a local cache directory keyed by
+//! distro/arch so repeat runs are fast after the first.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::config::Profile;
+
+#[derive(Debug, Clone)]
+pub struct BuildTarget {
+    pub distro: String,
+    pub arch: String,
+    pub dockerfile: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct BuildResult {
+    pub target: BuildTarget,
+    pub success: bool,
+    pub output: String,
+}
+
+/// expands each profile's (possibly comma-separated) `arch` field into one
+/// `BuildTarget` per distro/arch pair, pointing at the `Dockerfile.<distro>`
+/// this crate already generates.
+pub fn expand_matrix(profiles: &[Profile]) -> Vec<BuildTarget> {
+    let mut targets = Vec::new();
+    for profile in profiles {
+        let dockerfile = PathBuf::from(format!("Dockerfile.{}", profile.distro));
+        for arch in profile.arch.split(',').map(|a| a.trim()).filter(|a| !a.is_empty()) {
+            targets.push(BuildTarget {
+                distro: profile.distro.clone(),
+                arch: arch.to_string(),
+                dockerfile: dockerfile.clone(),
+            });
+        }
+    }
+    targets
+}
+
+/// runs `targets` through a bounded pool of `max_concurrency` worker
+/// threads, each invoking `docker buildx build` for its target, and returns
+/// one `BuildResult` per target (order not guaranteed to match `targets`).
+pub fn run_matrix(targets: Vec<BuildTarget>, max_concurrency: usize, cache_dir: &Path) -> Vec<BuildResult> {
+    let queue = Arc::new(Mutex::new(targets));
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let worker_count = max_concurrency.max(1);
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            let queue = Arc::clone(&queue);
+            let results = Arc::clone(&results);
+            scope.spawn(move || loop {
+                let target = {
+                    let mut queue = queue.lock().unwrap();
+                    queue.pop()
+                };
+                let Some(target) = target else {
+                    break;
+                };
+                let result = build_target(&target, cache_dir);
+                results.lock().unwrap().push(result);
+            });
+        }
+    });
+
+    Arc::try_unwrap(results).unwrap().into_inner().unwrap()
+}
+
+fn build_target(target: &BuildTarget, cache_dir: &Path) -> BuildResult {
+    let cache_path = cache_dir.join(format!("{}-{}", target.distro, target.arch));
+    let tag = format!("dnv-{}-{}:latest", target.distro, target.arch);
+
+    let output = Command::new("docker")
+        .arg("buildx")
+        .arg("build")
+        .arg("--platform")
+        .arg(format!("linux/{}", target.arch))
+        .arg("-f")
+        .arg(&target.dockerfile)
+        .arg("-t")
+        .arg(&tag)
+        .arg(format!("--cache-from=type=local,src={}", cache_path.display()))
+        .arg(format!("--cache-to=type=local,dest={},mode=max", cache_path.display()))
+        .arg(".")
+        .output();
+
+    match output {
+        Ok(output) => BuildResult {
+            target: target.clone(),
+            success: output.status.success(),
+            output: format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ),
+        },
+        Err(e) => BuildResult {
+            target: target.clone(),
+            success: false,
+            output: format!("failed to execute docker: {}", e),
+        },
+    }
+}
+
+/// prints a one-line-per-target pass/fail summary and returns whether every
+/// target succeeded.
+pub fn summarize(results: &[BuildResult]) -> bool {
+    let mut all_succeeded = true;
+    for result in results {
+        if result.success {
+            println!("ok    {}/{}", result.target.distro, result.target.arch);
+        } else {
+            all_succeeded = false;
+            println!("FAIL  {}/{}", result.target.distro, result.target.arch);
+        }
+    }
+    let failed = results.iter().filter(|r| !r.success).count();
+    println!("{}/{} targets succeeded", results.len() - failed, results.len());
+    all_succeeded
+}
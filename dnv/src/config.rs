@@ -0,0 +1,146 @@
+//! Declarative build profiles.
+//!
+//! `Profile`/`Profiles` model a target environment (distro/arch/user, plus
+//! the volumes/tools/dotfiles to provision), but until this module they were
+//! never populated from anything -- `main` hardcoded a single Ubuntu image.
+//! `Profiles::load` deserializes a YAML (or TOML, picked by file extension)
+//! config so a user can declare several target environments in one file and
+//! generate a Dockerfile per entry in a single run.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub distro: String,
+    pub arch: String,
+    pub user: String,
+
+    #[serde(default)]
+    pub volumes: Option<HashMap<String, String>>,
+
+    /// flat list of tool names to provision, the same shape the dotfiles
+    /// installers use for their package lists.
+    #[serde(default)]
+    pub tools: Vec<String>,
+
+    /// git URLs cloned into `$HOME` as the provisioning user, after `USER
+    /// $USERNAME` so the checkout is owned by that user without an extra
+    /// `chown` step.
+    #[serde(default)]
+    pub dotfiles: Vec<String>,
+
+    /// extensions to install when `tools` includes the `editor-extensions`
+    /// feature flag, e.g. `rust-lang.rust-analyzer`.
+    #[serde(default)]
+    pub editor_extensions: Vec<String>,
+
+    /// whether to include the docker-ce-cli install block in the generated
+    /// image.
+    #[serde(default)]
+    pub docker: bool,
+
+    /// tunes the distro's baseline `sysutils_packages`/`gcc_package` set
+    /// without forking the distro config, the way Kolla's `customizable`
+    /// hook tunes a base package list per deployment.
+    #[serde(default)]
+    pub packages: PackageOverrides,
+
+    /// candidate mirror hosts to probe for the docker/package repositories
+    /// baked into the generated Dockerfile, so users behind regional
+    /// networks get a reachable, fast mirror instead of a hardcoded one.
+    #[serde(default)]
+    pub mirrors: MirrorConfig,
+
+    /// pulls in the distro's curated static-build package group (e.g.
+    /// Alpine's `-static`/`-dev` pairs), for producing statically linkable
+    /// build environments. A no-op on distros with no such preset.
+    #[serde(default)]
+    pub static_build: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct PackageOverrides {
+    /// added to the distro defaults.
+    #[serde(default)]
+    pub append: Vec<String>,
+
+    /// replaces the distro defaults outright, before `append`/`remove` are
+    /// applied.
+    #[serde(default)]
+    pub r#override: Vec<String>,
+
+    /// dropped from the resolved set, applied last so it can also strip an
+    /// entry that `append` just added.
+    #[serde(default)]
+    pub remove: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MirrorConfig {
+    /// candidates for the docker-ce repo host embedded in `docker_install`.
+    #[serde(default = "MirrorConfig::default_docker_mirrors")]
+    pub docker: Vec<String>,
+
+    /// candidates for the distro package mirror embedded in `mirror_setup`.
+    /// empty means "don't probe, keep the distro default".
+    #[serde(default)]
+    pub package: Vec<String>,
+}
+
+impl MirrorConfig {
+    fn default_docker_mirrors() -> Vec<String> {
+        vec![
+            String::from("download.docker.com"),
+            String::from("mirrors.aliyun.com/docker-ce"),
+            String::from("mirror.azure.cn/docker-ce"),
+        ]
+    }
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        MirrorConfig {
+            docker: Self::default_docker_mirrors(),
+            package: Vec::new(),
+        }
+    }
+}
+
+impl PackageOverrides {
+    /// resolves `defaults` against this override block: `override` (if
+    /// non-empty) replaces `defaults` as the starting set, `append` is added,
+    /// then `remove` is stripped.
+    pub fn resolve(&self, defaults: Vec<String>) -> Vec<String> {
+        let mut resolved = if self.r#override.is_empty() {
+            defaults
+        } else {
+            self.r#override.clone()
+        };
+        resolved.extend(self.append.iter().cloned());
+        resolved.retain(|pkg| !self.remove.contains(pkg));
+        resolved
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    pub fn load(path: &Path) -> Result<Profiles, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read profiles config {:?}: {}", path, e))?;
+
+        let profiles = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content)
+                .map_err(|e| format!("failed to parse {:?} as toml: {}", path, e))?,
+            _ => serde_yaml::from_str(&content)
+                .map_err(|e| format!("failed to parse {:?} as yaml: {}", path, e))?,
+        };
+
+        Ok(profiles)
+    }
+}
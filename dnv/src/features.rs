@@ -0,0 +1,63 @@
+//! Opt-in provisioning feature flags layered over `Profile.tools`.
+//!
+//! Most entries in `tools` are plain package names that flow straight into
+//! `DistroConfig::install`, but a handful of names are recognized as
+//! feature flags instead -- `rust`, `go`, `docker`, and `editor-extensions`
+//! -- each expanding into its own distro-appropriate `RUN` block rather
+//! than a package-manager line, the way the dotfiles installers treat
+//! "install rust" as a rustup bootstrap rather than an apt package.
+
+const KNOWN_FEATURES: &[&str] = &["rust", "go", "docker", "editor-extensions"];
+
+pub fn is_feature(tool: &str) -> bool {
+    KNOWN_FEATURES.contains(&tool)
+}
+
+/// renders the `RUN` block for a single feature flag, or `None` if `tool`
+/// isn't a recognized feature (it should fall through to plain package
+/// install) or -- for `editor-extensions` -- no extensions were declared.
+pub fn render(tool: &str, editor_extensions: &[String]) -> Option<String> {
+    match tool {
+        "rust" => Some(String::from(
+            "RUN curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y\n\
+             ENV PATH=\"$HOME/.cargo/bin:${PATH}\"",
+        )),
+        "go" => Some(String::from(
+            "ARG TARGETARCH\n\
+             RUN curl -fsSL https://go.dev/dl/go1.22.5.linux-${TARGETARCH}.tar.gz | sudo tar -C /usr/local -xz\n\
+             ENV PATH=\"/usr/local/go/bin:${PATH}\"",
+        )),
+        "docker" => Some(String::from(
+            "RUN curl -fsSL https://get.docker.com | sudo sh && sudo usermod -aG docker $USER",
+        )),
+        "editor-extensions" => {
+            if editor_extensions.is_empty() {
+                return None;
+            }
+            let installs = editor_extensions
+                .iter()
+                .map(|ext| format!("    code --install-extension {} --force", ext))
+                .collect::<Vec<_>>()
+                .join(" && \\\n");
+            Some(format!("RUN \\\n{}", installs))
+        }
+        _ => None,
+    }
+}
+
+/// renders the `RUN` block that clones each dotfiles URL into `$HOME`. Run
+/// after `USER $USERNAME` so each checkout is owned by the provisioning
+/// user without an extra `chown`.
+pub fn render_dotfiles(dotfiles: &[String]) -> String {
+    dotfiles
+        .iter()
+        .enumerate()
+        .map(|(i, url)| {
+            format!(
+                "RUN git clone --depth 1 {} $HOME/.dotfiles-{}  && \\\n    cp -rT $HOME/.dotfiles-{} $HOME",
+                url, i, i
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
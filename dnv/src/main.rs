@@ -1,4 +1,8 @@
-use std::collections::HashMap;
+mod build;
+mod config;
+mod features;
+mod mirror;
+mod template;
 
 trait Dedent {
     fn dedent(&self) -> String;
@@ -32,17 +36,36 @@ impl Dedent for String {
     }
 }
 
-struct DockerFileBuilder {
-    dockerfile_template_base: String,
+/// the package family a distro belongs to. Collapsing `DistroConfigBuilder`'s
+/// per-distro methods onto this dimension (the way Kolla moved from
+/// per-distro `if` checks to a `base_package_type` hook) means adding a new
+/// deb- or rpm-based distro is a small data change instead of a new config
+/// method with its own copy of the install/update/docker-install commands.
+#[derive(Clone, Copy, PartialEq)]
+enum PackageType {
+    Deb,
+    Rpm,
+    Apk,
 }
 
+impl std::fmt::Display for PackageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageType::Deb => write!(f, "deb"),
+            PackageType::Rpm => write!(f, "rpm"),
+            PackageType::Apk => write!(f, "apk"),
+        }
+    }
+}
+
+#[derive(Clone)]
 struct DistroConfig {
     name: String,
-    format: String,
     base_image: String,
     commands: DistroSpecificCommands,
 }
 
+#[derive(Clone)]
 struct DistroSpecificCommands {
     pkg_install: String,
     pkg_install_flags: Option<String>,
@@ -51,6 +74,15 @@ struct DistroSpecificCommands {
     gcc_package: String,
     sysutils_packages: Vec<String>,
     mirror_setup: String,
+    /// the literal host baked into `mirror_setup` that a probed fastest
+    /// mirror should be substituted in place of. `None` for distros whose
+    /// `mirror_setup` doesn't bake in a swappable host (e.g. the RPM family's
+    /// `fastestmirror` plugin, or alma-minimal's no-op).
+    mirror_host_placeholder: Option<String>,
+    /// the curated "static build" package group (the netdata Dagger image
+    /// utilities' `-static`/`-dev` pairs), pulled in when a profile sets
+    /// `static_build = true`. Empty for distros with no such preset.
+    static_packages: Vec<String>,
 }
 
 impl DistroConfig {
@@ -86,6 +118,7 @@ enum Distro {
     Debian,
     Ubuntu,
     Fedora,
+    Alpine,
 }
 
 struct DistroConfigBuilder {
@@ -100,68 +133,124 @@ impl DistroConfigBuilder {
             Distro::Alma => self.alma_distro_config(),
             Distro::AlmaMinimal => self.alma_minimal_distro_config(),
             Distro::Fedora => self.fedora_distro_config(),
+            Distro::Alpine => self.alpine_distro_config(),
         }
     }
     
-    fn debian_distro_config(self) -> DistroConfig {
-        DistroConfig {
-            name: String::from("debian"),
-            pkg_format: String::from("deb"),
-            base_image: String::from("debian:bookworm"),
-            commands: DistroSpecificCommands {
+    /// the deb/rpm defaults that only vary, distro to distro, by the
+    /// docker-ce repo path segment (`debian`, `ubuntu`, `rhel`, ...).
+    /// distros that genuinely differ further (alma-minimal's `microdnf`)
+    /// override individual fields with `..Self::base_package_type_commands(..)`
+    /// rather than duplicating the whole block.
+    fn base_package_type_commands(
+        package_type: PackageType,
+        docker_repo_path: &str,
+    ) -> DistroSpecificCommands {
+        match package_type {
+            PackageType::Deb => DistroSpecificCommands {
                 pkg_install: String::from("sudo apt install -y"),
                 pkg_install_flags: None,
                 pkg_update: String::from("apt update && apt upgrade -y"),
-                docker_install: r#"
+                docker_install: format!(
+                    r#"
             sudo install -m 0755 -d /etc/apt/keyrings && \\
-            sudo curl -fsSL https://download.docker.com/linux/debian/gpg -o /etc/apt/keyrings/docker.asc && \\
+            sudo curl -fsSL https://download.docker.com/linux/{repo}/gpg -o /etc/apt/keyrings/docker.asc && \\
             sudo chmod a+r /etc/apt/keyrings/docker.asc && \\
             echo \\
-                "deb [arch=$(dpkg --print-architecture) signed-by=/etc/apt/keyrings/docker.asc] https://download.docker.com/linux/debian \\
+                "deb [arch=$(dpkg --print-architecture) signed-by=/etc/apt/keyrings/docker.asc] https://download.docker.com/linux/{repo} \\
                 $(. /etc/os-release && echo "$VERSION_CODENAME") stable" | \\
                 sudo tee /etc/apt/sources.list.d/docker.list > /dev/null && \\
             sudo apt-get update -y && \\
             sudo apt-get install docker-ce-cli -y
-            "#.dedent(),
+            "#,
+                    repo = docker_repo_path
+                )
+                .dedent(),
                 gcc_package: String::from("gcc"),
                 sysutils_packages: vec![String::from("procps"), String::from("iproute2")],
-                mirror_setup: String::from("echo \"Acquire::Retries \"3\";\" > /etc/apt/apt.conf.d/80-retries"),
+                mirror_setup: String::from(
+                    "echo \"Acquire::Retries \"3\";\" > /etc/apt/apt.conf.d/80-retries",
+                ),
+                mirror_host_placeholder: None,
+                static_packages: Vec::new(),
             },
-        }
-    }
-    
-    fn rpm_base_commands() -> DistroSpecificCommands {
-        DistroSpecificCommands {
-            pkg_install: String::from("sudo dnf install -y"),
-            pkg_install_flags: None,
-            pkg_update: String::from("dnf update -y"),
-            docker_install: r#"
+            PackageType::Rpm => DistroSpecificCommands {
+                pkg_install: String::from("sudo dnf install -y"),
+                pkg_install_flags: None,
+                pkg_update: String::from("dnf update -y"),
+                docker_install: format!(
+                    r#"
             sudo dnf -y install dnf-plugins-core && \\
-            sudo dnf config-manager --add-repo https://download.docker.com/linux/rhel/docker-ce.repo && \\
+            sudo dnf config-manager --add-repo https://download.docker.com/linux/{repo}/docker-ce.repo && \\
             sudo dnf install -y docker-ce-cli
-            "#.dedent(),
-            gcc_package: String::from("gcc"),
-            sysutils_packages: vec![String::from("procps"), String::from("iproute")],
-            mirror_setup: String::from("echo \"fastestmirror=True\" >> /etc/dnf/dnf.conf && echo \"max_parallel_downloads=10\" >> /etc/dnf/dnf.conf"),
+            "#,
+                    repo = docker_repo_path
+                )
+                .dedent(),
+                gcc_package: String::from("gcc"),
+                sysutils_packages: vec![String::from("procps"), String::from("iproute")],
+                mirror_setup: String::from(
+                    "echo \"fastestmirror=True\" >> /etc/dnf/dnf.conf && echo \"max_parallel_downloads=10\" >> /etc/dnf/dnf.conf",
+                ),
+                mirror_host_placeholder: None,
+                static_packages: Vec::new(),
+            },
+            PackageType::Apk => DistroSpecificCommands {
+                pkg_install: String::from("sudo apk add --no-cache"),
+                pkg_install_flags: None,
+                pkg_update: String::from("apk update"),
+                docker_install: String::from("sudo apk add --no-cache docker-cli"),
+                gcc_package: String::from("gcc"),
+                sysutils_packages: vec![String::from("procps"), String::from("iproute2")],
+                mirror_setup: String::from(
+                    "sed -i 's|dl-cdn.alpinelinux.org|mirrors.aliyun.com|g' /etc/apk/repositories",
+                ),
+                mirror_host_placeholder: Some(String::from("mirrors.aliyun.com")),
+                static_packages: vec![
+                    String::from("alpine-sdk"),
+                    String::from("cmake"),
+                    String::from("ninja"),
+                    String::from("zlib-static"),
+                    String::from("zlib-dev"),
+                    String::from("zstd-static"),
+                    String::from("zstd-dev"),
+                    String::from("libmnl-static"),
+                    String::from("libmnl-dev"),
+                ],
+            },
         }
     }
-    
+
+    fn alpine_distro_config(self) -> DistroConfig {
+        DistroConfig {
+            name: String::from("alpine"),
+            base_image: String::from("alpine:3.20"),
+            commands: Self::base_package_type_commands(PackageType::Apk, "alpine"),
+        }
+    }
+
+    fn debian_distro_config(self) -> DistroConfig {
+        DistroConfig {
+            name: String::from("debian"),
+            base_image: String::from("debian:bookworm"),
+            commands: Self::base_package_type_commands(PackageType::Deb, "debian"),
+        }
+    }
+
     fn alma_distro_config(self) -> DistroConfig {
         DistroConfig {
             name: String::from("alma"),
-            pkg_format: String::from("rpm"),
             base_image: String::from("almalinux:9"),
             commands: DistroSpecificCommands {
                 pkg_install_flags: Some(String::from("--skip-broken")),
-                ..Self::rpm_base_commands()
+                ..Self::base_package_type_commands(PackageType::Rpm, "rhel")
             },
         }
     }
-    
+
     fn alma_minimal_distro_config(self) -> DistroConfig {
         DistroConfig {
             name: String::from("alma-minimal"),
-            pkg_format: String::from("rpm"),
             base_image: String::from("almalinux:9-minimal"),
             commands: DistroSpecificCommands {
                 pkg_install: String::from("sudo microdnf install -y"),
@@ -171,126 +260,166 @@ impl DistroConfigBuilder {
             sudo microdnf -y install dnf-plugins-core && \\
             sudo dnf config-manager --add-repo https://download.docker.com/linux/rhel/docker-ce.repo && \\
             sudo microdnf install -y docker-ce-cli
-            "#.dedent(),
+            "#
+                .dedent(),
                 mirror_setup: String::from("true"),
-                ..Self::rpm_base_commands()
+                ..Self::base_package_type_commands(PackageType::Rpm, "rhel")
             },
         }
     }
-    
+
     fn fedora_distro_config(self) -> DistroConfig {
         DistroConfig {
             name: String::from("fedora"),
-            pkg_format: String::from("rpm"),
             base_image: String::from("fedora:41"),
-            commands: Self::rpm_base_commands(),
+            commands: Self::base_package_type_commands(PackageType::Rpm, "rhel"),
         }
     }
-    
+
     fn ubuntu_distro_config(self) -> DistroConfig {
         DistroConfig {
             name: String::from("ubuntu"),
-            pkg_format: String::from("deb"),
             base_image: String::from("ubuntu:22.04"),
             commands: DistroSpecificCommands {
-                pkg_install: String::from("sudo apt install -y"),
-                pkg_install_flags: None,
-                pkg_update: String::from("apt update && apt upgrade -y"),
-                docker_install: r#"
-            sudo install -m 0755 -d /etc/apt/keyrings && \\
-            sudo curl -fsSL https://download.docker.com/linux/ubuntu/gpg -o /etc/apt/keyrings/docker.asc && \\
-            sudo chmod a+r /etc/apt/keyrings/docker.asc && \\
-            echo \\
-                "deb [arch=$(dpkg --print-architecture) signed-by=/etc/apt/keyrings/docker.asc] https://download.docker.com/linux/ubuntu \\
-                $(. /etc/os-release && echo "$VERSION_CODENAME") stable" | \\
-                sudo tee /etc/apt/sources.list.d/docker.list > /dev/null && \\
-            sudo apt-get update -y && \\
-            sudo apt-get install docker-ce-cli -y
-            "#.dedent(),
-                gcc_package: String::from("gcc"),
-                sysutils_packages: vec![String::from("procps"), String::from("iproute2")],
-                mirror_setup: String::from("sed -i 's|http://archive.ubuntu.com|http://mirrors.ubuntu.com|g' /etc/apt/sources.list"),
+                mirror_setup: String::from(
+                    "sed -i 's|http://archive.ubuntu.com|http://mirrors.ubuntu.com|g' /etc/apt/sources.list",
+                ),
+                mirror_host_placeholder: Some(String::from("mirrors.ubuntu.com")),
+                ..Self::base_package_type_commands(PackageType::Deb, "ubuntu")
             },
         }
     }
-
-
 }
 
-// struct Dotfiles
-struct Profile {
-    distro: String,
-    arch: String,
-    user: String,
-    volumes: Option<HashMap<String, String>>,
-    tools: Vec<String>,
-    dotfiles: Vec<String>,
+impl Distro {
+    fn from_name(name: &str) -> Result<Distro, String> {
+        match name.to_lowercase().as_str() {
+            "debian" => Ok(Distro::Debian),
+            "ubuntu" => Ok(Distro::Ubuntu),
+            "alma" => Ok(Distro::Alma),
+            "alma-minimal" => Ok(Distro::AlmaMinimal),
+            "fedora" => Ok(Distro::Fedora),
+            "alpine" => Ok(Distro::Alpine),
+            other => Err(format!("unknown distro `{}`", other)),
+        }
+    }
 }
 
-struct Profiles {
-    profiles: Vec<Profile>
-}
+fn render_dockerfile(profile: &config::Profile) -> Result<String, String> {
+    let distro = Distro::from_name(&profile.distro)?;
+    let distro_config = DistroConfigBuilder { distro }.build();
 
-impl DockerFileBuilder {
-    fn new(mut self) -> Self {
-        self.dockerfile_template_base = r#"
-        # NOTE: This Dockerfile is generated. Do not edit manually.
-        FROM <$>base_image
-        SHELL ["/bin/bash", "-euo", "pipefail", "-c"]
-        ENV SHELL=/bin/bash
+    let workdir = format!("/home/{}", profile.user);
 
-        RUN <$>mirror_configure && \
-            <$>update && \
-            <$>install_sudo
+    let mut baseline = distro_config.commands.sysutils_packages.clone();
+    baseline.push(distro_config.commands.gcc_package.clone());
+    if profile.static_build {
+        baseline.extend(distro_config.commands.static_packages.iter().cloned());
+    }
+    let baseline = profile.packages.resolve(baseline);
 
-        ARG USERNAME=<$>username
-        ARG USER_UID=1000
-        ARG USER_GID=$USER_UID
+    let (feature_tools, package_tools): (Vec<String>, Vec<String>) = profile
+        .tools
+        .iter()
+        .cloned()
+        .partition(|tool| features::is_feature(tool));
 
-        RUN groupadd --gid $USER_GID $USERNAME \
-            && useradd --uid $USER_UID --gid $USER_GID -m $USERNAME \
-            && echo $USERNAME ALL=\(root\) NOPASSWD:ALL > /etc/sudoers.d/$USERNAME \
-            && chmod 0440 /etc/sudoers.d/$USERNAME
+    let mut tools = baseline;
+    tools.extend(package_tools);
+    let install_line = distro_config.clone().install(tools);
 
-        USER $USERNAME
+    let feature_blocks = feature_tools
+        .iter()
+        .filter_map(|tool| features::render(tool, &profile.editor_extensions))
+        .collect::<Vec<_>>()
+        .join("\n\n");
 
-        WORKDIR <$>workdir
+    let dotfiles_block = features::render_dotfiles(&profile.dotfiles);
 
-        ENV HOME=<$>workdir
+    let docker_host = mirror::fastest(&profile.mirrors.docker)
+        .unwrap_or_else(|| String::from("download.docker.com"));
+    let docker_install = distro_config
+        .commands
+        .docker_install
+        .replace("download.docker.com", &docker_host);
 
-        <$>tool_stages
+    let mirror_configure = match (
+        mirror::fastest(&profile.mirrors.package),
+        &distro_config.commands.mirror_host_placeholder,
+    ) {
+        (Some(host), Some(placeholder)) => {
+            distro_config.commands.mirror_setup.replace(placeholder, &host)
+        }
+        (Some(_), None) => {
+            eprintln!(
+                "warning: {} has no mirror host to substitute; ignoring profile.mirrors.package",
+                distro_config.name
+            );
+            distro_config.commands.mirror_setup.clone()
+        }
+        (None, _) => distro_config.commands.mirror_setup.clone(),
+    };
 
-        # SecretsUsedInArgOrEnv: Do not use ARG or ENV instructions for sensitive data
-        ARG PASSWORD=admin
-        RUN echo "${USERNAME}:${PASSWORD}" | sudo chpasswd
-    "#
-        .dedent();
-        self
-    }
+    let ctx = template::TemplateContext {
+        base_image: distro_config.base_image.clone(),
+        mirror_configure,
+        update: distro_config.commands.pkg_update.clone(),
+        install_sudo: format!("{} sudo", distro_config.commands.pkg_install),
+        install_line,
+        feature_blocks,
+        dotfiles_block,
+        username: profile.user.clone(),
+        workdir,
+        volumes: profile.volumes.clone().unwrap_or_default(),
+        include_docker_install: profile.docker,
+        docker_install,
+    };
+
+    template::render(&ctx)
 }
 
 fn main() {
-    let base_image = "ubuntu:22.04";
-    let mirror_configure =
-        "sed -i 's|http://archive.ubuntu.com|http://mirrors.ubuntu.com|g' /etc/apt/sources.list";
-    let update = "apt-get update";
-    let install_sudo = "apt-get install -y sudo";
-    let workdir = "/workspace";
-    let tool_stages = "RUN apt-get install -y git curl vim";
-
-    let template = DockerFileBuilder {
-        dockerfile_template_base: String::from(""),
+    let config_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "profiles.yaml".to_string());
+
+    let profiles = match config::Profiles::load(std::path::Path::new(&config_path)) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for profile in &profiles.profiles {
+        match render_dockerfile(profile) {
+            Ok(dockerfile) => {
+                let out_path = format!("Dockerfile.{}", profile.distro);
+                if let Err(e) = std::fs::write(&out_path, &dockerfile) {
+                    eprintln!("error: failed to write {}: {}", out_path, e);
+                    std::process::exit(1);
+                }
+                println!("wrote {}", out_path);
+            }
+            Err(e) => {
+                eprintln!(
+                    "error: failed to render dockerfile for profile `{}`: {}",
+                    profile.distro, e
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if std::env::args().nth(2).as_deref() == Some("build") {
+        let targets = build::expand_matrix(&profiles.profiles);
+        let concurrency = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+        let cache_dir = std::path::Path::new(".dnv-cache");
+        let results = build::run_matrix(targets, concurrency, cache_dir);
+        if !build::summarize(&results) {
+            std::process::exit(1);
+        }
     }
-    .new()
-    .dockerfile_template_base;
-
-    let formatted = template
-        .replace("<$>base_image", base_image)
-        .replace("<$>mirror_configure", mirror_configure)
-        .replace("<$>update", update)
-        .replace("<$>install_sudo", install_sudo)
-        .replace("<$>workdir", workdir)
-        .replace("<$>tool_stages", tool_stages);
-
-    println!("{}", formatted);
 }
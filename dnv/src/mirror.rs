@@ -0,0 +1,47 @@
+//! Fastest-mirror probing for the docker/package repository hosts baked
+//! into a generated Dockerfile.
+//!
+//! `mirror_setup` and the docker-install URLs used to hardcode a single
+//! host per distro (always `download.docker.com`, always
+//! `mirrors.ubuntu.com`). `fastest` issues a lightweight request to each
+//! candidate, keeps the ones that answered reachable (200 or a 301
+//! redirect), and returns the lowest-latency survivor so the generated
+//! Dockerfile can substitute it in instead.
+
+use std::time::{Duration, Instant};
+
+struct Probe {
+    host: String,
+    latency: Duration,
+}
+
+/// probes `candidates` (bare hosts, e.g. `download.docker.com` or
+/// `mirrors.aliyun.com/docker-ce`) over HTTPS and returns the lowest-latency
+/// one that answered 200 or 301. Returns `None` if every candidate was
+/// unreachable, in which case callers should fall back to the distro
+/// default rather than failing the whole generation.
+pub fn fastest(candidates: &[String]) -> Option<String> {
+    let agent = ureq::AgentBuilder::new()
+        .redirects(0)
+        .timeout(Duration::from_secs(3))
+        .build();
+
+    let mut reachable = Vec::new();
+    for host in candidates {
+        let url = format!("https://{}/", host.trim_end_matches('/'));
+        let start = Instant::now();
+        let status = match agent.get(&url).call() {
+            Ok(response) => Some(response.status()),
+            Err(ureq::Error::Status(code, _)) => Some(code),
+            Err(ureq::Error::Transport(_)) => None,
+        };
+        if matches!(status, Some(200) | Some(301)) {
+            reachable.push(Probe {
+                host: host.clone(),
+                latency: start.elapsed(),
+            });
+        }
+    }
+
+    reachable.into_iter().min_by_key(|p| p.latency).map(|p| p.host)
+}
@@ -0,0 +1,105 @@
+//! Jinja-style Dockerfile templating.
+//!
+//! The old scheme filled a template purely with chained `str::replace("<$>...")`,
+//! which can't express the repetition and branching real Dockerfile
+//! generation needs (one `VOLUME` per declared volume, an optional
+//! docker-install block). minijinja lets the base template loop over
+//! `volumes` and branch on `include_docker_install` instead, the way
+//! Kolla's Jinja templates iterate a users dictionary to emit one
+//! `groupadd && useradd` per user.
+
+use minijinja::{context, Environment};
+use std::collections::HashMap;
+
+const BASE_TEMPLATE: &str = r#"# NOTE: This Dockerfile is generated. Do not edit manually.
+FROM {{ base_image }}
+SHELL ["/bin/bash", "-euo", "pipefail", "-c"]
+ENV SHELL=/bin/bash
+
+RUN {{ mirror_configure }} && \
+    {{ update }} && \
+    {{ install_sudo }}
+
+ARG USERNAME={{ username }}
+ARG USER_UID=1000
+ARG USER_GID=$USER_UID
+
+RUN groupadd --gid $USER_GID $USERNAME \
+    && useradd --uid $USER_UID --gid $USER_GID -m $USERNAME \
+    && echo $USERNAME ALL=\(root\) NOPASSWD:ALL > /etc/sudoers.d/$USERNAME \
+    && chmod 0440 /etc/sudoers.d/$USERNAME
+
+USER $USERNAME
+
+WORKDIR {{ workdir }}
+
+ENV HOME={{ workdir }}
+
+RUN {{ install_line }}
+{% if feature_blocks %}
+{{ feature_blocks }}
+{% endif %}
+{% if dotfiles_block %}
+{{ dotfiles_block }}
+{% endif %}
+{% for name, path in volumes|items %}
+VOLUME {{ path }}
+{% endfor %}
+{% if include_docker_install %}
+RUN {{ docker_install }}
+{% endif %}
+
+# SecretsUsedInArgOrEnv: Do not use ARG or ENV instructions for sensitive data
+ARG PASSWORD=admin
+RUN echo "${USERNAME}:${PASSWORD}" | sudo chpasswd
+"#;
+
+pub struct TemplateContext {
+    pub base_image: String,
+    pub mirror_configure: String,
+    pub update: String,
+    pub install_sudo: String,
+    /// the fully resolved, single `DistroConfig::install` command line
+    /// covering the distro's sysutils/gcc baseline (as tuned by the
+    /// profile's `packages` overrides) plus the profile's `tools` entries
+    /// that are not feature flags (those go to `feature_blocks`).
+    pub install_line: String,
+    /// `RUN` blocks for `tools` entries recognized as feature flags
+    /// (`rust`, `go`, `docker`, `editor-extensions`) rather than plain
+    /// package names. Empty string if `tools` had none.
+    pub feature_blocks: String,
+    /// `RUN` block cloning each `Profile.dotfiles` entry into `$HOME`.
+    /// Empty string if no dotfiles were declared.
+    pub dotfiles_block: String,
+    pub username: String,
+    pub workdir: String,
+    pub volumes: HashMap<String, String>,
+    pub include_docker_install: bool,
+    pub docker_install: String,
+}
+
+pub fn render(ctx: &TemplateContext) -> Result<String, String> {
+    let mut env = Environment::new();
+    env.add_template("dockerfile", BASE_TEMPLATE)
+        .map_err(|e| format!("failed to parse dockerfile template: {}", e))?;
+
+    let tmpl = env
+        .get_template("dockerfile")
+        .map_err(|e| format!("failed to load dockerfile template: {}", e))?;
+
+    tmpl.render(context! {
+        base_image => ctx.base_image,
+        mirror_configure => ctx.mirror_configure,
+        update => ctx.update,
+        install_sudo => ctx.install_sudo,
+        install_line => ctx.install_line,
+        feature_blocks => ctx.feature_blocks,
+        dotfiles_block => ctx.dotfiles_block,
+        username => ctx.username,
+        workdir => ctx.workdir,
+        volumes => ctx.volumes,
+        include_docker_install => ctx.include_docker_install,
+        docker_install => ctx.docker_install,
+    })
+    .map_err(|e| format!("failed to render dockerfile template: {}", e))
+}
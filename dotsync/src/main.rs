@@ -3,13 +3,20 @@ use clap::Subcommand;
 use env_logger::Env;
 use log::debug;
 use log::info;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
 use std::os::unix::fs::PermissionsExt;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+mod source;
+use source::Fetcher;
 
 /// Philosophy: Keep Things Simple and Clear
 ///
@@ -36,6 +43,37 @@ struct History {
     created_at: String,
     backup: String,
     files: Vec<String>,
+
+    /// content hash per file, recorded only in `Mode::Copied` so `status`
+    /// can detect local drift instead of just checking `is_symlink()`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hashes: Option<HashMap<String, String>>,
+}
+
+/// how a profile's files are materialized onto disk.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+enum Mode {
+    /// symlink into the repository (the default).
+    Linked,
+    /// copy the file's contents out of the repository. useful for tools
+    /// that rewrite their config file in place, or filesystems without
+    /// symlink support.
+    Copied,
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Linked
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Linked => write!(f, "linked"),
+            Mode::Copied => write!(f, "copied"),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -49,6 +87,9 @@ struct State {
     backup_path: String,
     profiles: HashMap<String, Vec<String>>,
 
+    #[serde(default)]
+    mode: Mode,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     active_profile: Option<String>,
 
@@ -59,7 +100,7 @@ struct State {
     source_type: Option<SourceType>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 enum SourceType {
     Zip,
     GitHttps,
@@ -101,11 +142,84 @@ impl State {
     fn read_state_file() -> Result<State, Box<dyn std::error::Error>> {
         let path = expand_home(Self::STATE_FILE_PATH);
         let content = fs::read_to_string(path)?;
-        let state = serde_json::from_str(&content)?;
+        let state: State = serde_json::from_str(&content)?;
+        state
+            .validate()
+            .map_err(|problems| problems.join("\n"))?;
         debug!("state file read successfully");
         Ok(state)
     }
 
+    /// Validate the config/state for problems that would otherwise surface
+    /// later as a confusing half-applied setup: an empty url, a `source_type`
+    /// override that disagrees with what we'd detect from the url, an
+    /// `active_profile` that isn't declared, a file path that escapes `$HOME`,
+    /// or the same file declared in two profiles. Every problem found is
+    /// returned, not just the first, so the user can fix them all in one pass.
+    fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        if self.url.trim().is_empty() {
+            problems.push("`url` must not be empty".to_string());
+        }
+
+        if let Some(declared) = self.source_type {
+            let detected = detect_source_type(&self.url);
+            if declared != detected {
+                problems.push(format!(
+                    "`source_type` is set to `{}` but `{}` looks like a `{}` source",
+                    declared, self.url, detected
+                ));
+            }
+        }
+
+        if let Some(active) = &self.active_profile {
+            if !self.profiles.contains_key(active) {
+                problems.push(format!(
+                    "`active_profile` is set to `{}`, which is not a key in `profiles`",
+                    active
+                ));
+            }
+        }
+
+        let mut owner_of: HashMap<&str, &str> = HashMap::new();
+        for (profile_name, files) in &self.profiles {
+            for file in files {
+                let path = PathBuf::from(file);
+                if path.is_absolute() {
+                    problems.push(format!(
+                        "profile `{}` references absolute path `{}`; paths must be relative to $HOME",
+                        profile_name, file
+                    ));
+                }
+                if path
+                    .components()
+                    .any(|c| matches!(c, std::path::Component::ParentDir))
+                {
+                    problems.push(format!(
+                        "profile `{}` references path `{}` that escapes its directory via `..`",
+                        profile_name, file
+                    ));
+                }
+                if let Some(other_profile) = owner_of.insert(file.as_str(), profile_name.as_str())
+                {
+                    if other_profile != profile_name {
+                        problems.push(format!(
+                            "file `{}` is declared in both profile `{}` and profile `{}`",
+                            file, other_profile, profile_name
+                        ));
+                    }
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
     fn set_active_profile(mut self, profile: &str) -> Self {
         self.active_profile = Some(profile.to_owned());
         self
@@ -185,7 +299,7 @@ enum Commands {
     ///
     /// reads the state file and creates symlinks for all files in the specified profile.
     /// before creating symlinks, existing files are backed up to a timestamped zip file.
-    /// if the specified profile is not found, falls back to 'default' profile.
+    /// errors out, listing the available profiles, if the specified profile is not found.
     ///
     /// example: dotsync setup --profile=default
     Setup {
@@ -194,6 +308,11 @@ enum Commands {
 
         #[arg(long)]
         dry_run: bool,
+
+        /// how to materialize files, `linked` (symlinks) or `copied`; overrides
+        /// and persists over whatever mode is already recorded in state
+        #[arg(long, value_enum)]
+        mode: Option<Mode>,
     },
 
     /// show the current active profile and synced dotfiles
@@ -206,8 +325,11 @@ enum Commands {
 
     /// refresh dotfiles from the repository
     ///
-    /// pulls latest changes from the repository and updates symlinks.
-    /// useful for keeping your dotfiles in sync across machines.
+    /// pulls latest changes from the repository and updates symlinks. if the
+    /// local checkout appears corrupted (e.g. a previous clone was
+    /// interrupted), automatically re-clones it once. network/auth errors
+    /// are surfaced directly instead of triggering a re-clone. zip sources
+    /// are refreshed by re-downloading and re-extracting the archive.
     ///
     /// example: dotsync refresh
     Refresh {},
@@ -220,14 +342,54 @@ enum Commands {
     /// example: dotsync backup
     Backup {},
 
+    /// restore dotfiles from a backup archive
+    ///
+    /// extracts each stored file from a backup zip back to its original
+    /// location. defaults to the most recent backup recorded in the state
+    /// file's history if `--archive` is not given. refuses to overwrite an
+    /// existing symlink unless `--force` is passed.
+    ///
+    /// example: dotsync restore --archive=~/.dotsync-backups/backup_20260101120000.zip
+    Restore {
+        #[arg(long)]
+        archive: Option<PathBuf>,
+
+        #[arg(long)]
+        force: bool,
+    },
+
     /// remove all symlinks for the active profile
     ///
     /// removes all symlinks created by dotsync for the active profile.
-    /// this does not delete your actual dotfiles, only the symlinks.
+    /// this does not delete your actual dotfiles, only the symlinks. in
+    /// `Mode::Copied`, the materialized files *are* your actual dotfiles, so
+    /// they're left alone unless `--force` is passed.
     /// the state file is also removed after cleanup.
     ///
     /// example: dotsync destroy
-    Destroy {},
+    Destroy {
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// watch the repository and live dotfiles, and keep them in sync
+    ///
+    /// runs in the foreground, monitoring both the repository at `State::path`
+    /// and the live symlink targets of the active profile. a change inside
+    /// the repository re-validates/recreates all of the active profile's
+    /// symlinks; a change
+    /// to a live dotfile is logged and, with --push, committed and pushed
+    /// back to the repository. the state file is reloaded automatically if
+    /// it changes on disk.
+    ///
+    /// example: dotsync watch --push --interval=300
+    Watch {
+        #[arg(long)]
+        push: bool,
+
+        #[arg(long)]
+        interval: Option<u64>,
+    },
 }
 
 fn expand_home(path: &str) -> PathBuf {
@@ -250,82 +412,104 @@ fn git_is_installed() -> bool {
         .unwrap_or(false)
 }
 
-fn clone_repository(url: &str, branch: &str, path: &PathBuf) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("failed to create directories: {}", e))?;
-    }
-
-    let mut cmd = Command::new("git");
-    cmd.arg("clone");
-
-    if !branch.is_empty() {
-        cmd.arg("--branch").arg(branch);
-    }
-
-    cmd.arg(url).arg(path);
+/// classification of a failed `git pull`, used to decide whether `refresh`
+/// should surface the error as-is or treat it as local corruption and
+/// re-clone.
+enum GitPullErrorClass {
+    /// network or auth failure: retrying via re-clone would not help, and
+    /// blowing away the repo would be actively harmful (it'd throw away
+    /// uncommitted local state for no benefit).
+    Network,
+    /// the fetch succeeded but the target revision/working tree could not
+    /// be resolved, implying the on-disk `.git` state itself is broken.
+    Corruption,
+}
 
-    let output = cmd
-        .output()
-        .map_err(|e| format!("failed to execute git: {}", e))?;
+struct GitPullError {
+    class: GitPullErrorClass,
+    message: String,
+}
 
-    if output.status.success() {
-        info!("successfully cloned repository to {:?}", path);
-        Ok(())
+fn classify_git_error(stderr: &str) -> GitPullErrorClass {
+    let lower = stderr.to_lowercase();
+    if lower.contains("could not resolve host")
+        || lower.contains("could not read from remote repository")
+        || lower.contains("connection refused")
+        || lower.contains("connection timed out")
+        || lower.contains("timed out")
+        || lower.contains("permission denied (publickey)")
+        || lower.contains("authentication failed")
+        || lower.contains("443")
+    {
+        GitPullErrorClass::Network
     } else {
-        Err(format!(
-            "git clone failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ))
+        GitPullErrorClass::Corruption
     }
 }
 
-fn download_and_extract_zip(url: &str, path: &PathBuf) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("failed to create directories: {}", e))?;
-    }
-
-    let temp_zip = path.with_extension("zip.tmp");
-
-    let output = Command::new("curl")
-        .arg("-L")
-        .arg("-o")
-        .arg(&temp_zip)
-        .arg(url)
-        .output()
-        .map_err(|e| format!("failed to execute curl: {}", e))?;
+fn pull_repository(path: &PathBuf, branch: &str) -> Result<(), GitPullError> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(path).arg("pull").arg("--ff-only");
 
-    if !output.status.success() {
-        return Err(format!(
-            "curl failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+    if !branch.is_empty() {
+        cmd.arg("origin").arg(branch);
     }
 
-    info!("downloaded zip file from {}", url);
-
-    let output = Command::new("unzip")
-        .arg("-q")
-        .arg(&temp_zip)
-        .arg("-d")
-        .arg(path)
-        .output()
-        .map_err(|e| format!("failed to execute unzip: {}", e))?;
+    let output = cmd.output().map_err(|e| GitPullError {
+        class: GitPullErrorClass::Network,
+        message: format!("failed to execute git: {}", e),
+    })?;
 
-    if !output.status.success() {
-        let _ = std::fs::remove_file(&temp_zip);
-        return Err(format!(
-            "unzip failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+    if output.status.success() {
+        return Ok(());
     }
 
-    std::fs::remove_file(&temp_zip)
-        .map_err(|e| format!("failed to remove temporary zip file: {}", e))?;
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    Err(GitPullError {
+        class: classify_git_error(&stderr),
+        message: stderr,
+    })
+}
 
-    info!("successfully extracted zip to {:?}", path);
-    Ok(())
+/// Pull the repository at `path`, self-healing from local corruption.
+///
+/// A network/auth failure is surfaced directly since re-cloning would not
+/// fix it and would discard the (presumably fine) local checkout for
+/// nothing. If the fetch itself fails to resolve the branch, or the
+/// subsequent `--ff-only` merge/working-tree update fails, we treat that as
+/// local corruption: wipe `path` and perform one fresh clone. No further
+/// retry is attempted after the re-clone so a persistently broken remote
+/// can't put us in a loop.
+fn refresh_git_repo(url: &str, branch: &str, path: &PathBuf) -> Result<(), String> {
+    match pull_repository(path, branch) {
+        Ok(()) => {
+            info!("refresh: pulled latest changes ({:?})", path);
+            Ok(())
+        }
+        Err(GitPullError {
+            class: GitPullErrorClass::Network,
+            message,
+        }) => Err(format!(
+            "refresh: network/auth error while pulling, not re-cloning: {}",
+            message
+        )),
+        Err(GitPullError {
+            class: GitPullErrorClass::Corruption,
+            message,
+        }) => {
+            info!(
+                "refresh: local repository at {:?} looks corrupted ({}), re-cloning",
+                path, message
+            );
+            fs::remove_dir_all(path)
+                .map_err(|e| format!("failed to remove corrupted repo at {:?}: {}", path, e))?;
+            source::GitFetcher
+                .fetch(url, branch, path)
+                .map_err(|e| format!("re-clone after corruption recovery failed: {}", e))?;
+            info!("refresh: recovered via fresh clone of {:?}", path);
+            Ok(())
+        }
+    }
 }
 
 fn init(config_path: Option<std::path::PathBuf>) -> Result<(), String> {
@@ -337,25 +521,23 @@ fn init(config_path: Option<std::path::PathBuf>) -> Result<(), String> {
     let config: State = serde_json::from_str(&config_content)
         .map_err(|e| format!("failed to parse config file: {}", e))?;
 
+    if let Err(problems) = config.validate() {
+        for problem in &problems {
+            eprintln!("error: {}", problem);
+        }
+        return Err(format!(
+            "config file is invalid ({} problem(s) found, see above)",
+            problems.len()
+        ));
+    }
+
     let source_type = detect_source_type(&config.url);
     info!("detected source type: {}", source_type);
 
     let repo_path = expand_home(&config.path);
+    let branch = config.branch.as_deref().unwrap_or("");
 
-    match source_type {
-        SourceType::Zip => {
-            info!("downloading zip from {}", config.url);
-            download_and_extract_zip(&config.url, &repo_path)
-        }
-        SourceType::GitHttps | SourceType::GitSsh => {
-            if !git_is_installed() {
-                return Err("git is not installed. please install git to proceed.".to_string());
-            }
-            let branch = config.branch.as_deref().unwrap_or("");
-            info!("cloning repository from {} to {:?}", config.url, repo_path);
-            clone_repository(&config.url, branch, &repo_path)
-        }
-    }?;
+    source::fetcher_for(source_type).fetch(&config.url, branch, &repo_path)?;
 
     let config = config.set_source_type(source_type);
     config
@@ -365,37 +547,146 @@ fn init(config_path: Option<std::path::PathBuf>) -> Result<(), String> {
     Ok(())
 }
 
-// TODO: implement rollback logic - if symlink creation fails midway,
-// already-created symlinks should be cleaned up to avoid orphaned state
-fn create_symlinks(files: &Vec<String>, source_dir: &str) -> Result<String, String> {
+fn hash_file(path: &PathBuf) -> Result<String, String> {
+    let content =
+        fs::read(path).map_err(|e| format!("failed to read file {:?} for hashing: {}", path, e))?;
+    let mut hasher = Sha256::new();
+    hasher.update(&content);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// copy `source` to `target`, preserving the source file's permissions, and
+/// return its content hash so drift can be detected later by `status`.
+fn copy_file(source: &PathBuf, target: &PathBuf) -> Result<String, String> {
+    fs::copy(source, target)
+        .map_err(|e| format!("failed to copy {:?} -> {:?}: {}", source, target, e))?;
+
+    let permissions = fs::metadata(source)
+        .map_err(|e| format!("failed to read permissions of {:?}: {}", source, e))?
+        .permissions();
+    fs::set_permissions(target, permissions)
+        .map_err(|e| format!("failed to set permissions on {:?}: {}", target, e))?;
+
+    hash_file(source)
+}
+
+/// if `target` already exists and isn't a symlink, back it up into a new
+/// timestamped zip under `backup_dir` and remove it, rather than silently
+/// clobbering a real file. this zip is separate from the one `setup`'s
+/// `create_backup` writes, and its path is not recorded in the state history.
+fn guard_against_clobber(target: &PathBuf, file: &str, backup_dir: &str) -> Result<(), String> {
+    if !target.exists() && !target.is_symlink() {
+        return Ok(());
+    }
+    if target.is_symlink() {
+        // already one of ours (or at least a symlink) from a previous run;
+        // it's about to be replaced below.
+        return Ok(());
+    }
+
+    info!(
+        "{:?} already exists and is not a symlink, backing it up before overwriting",
+        target
+    );
+    archive_files(&vec![file.to_string()], backup_dir)?;
+    fs::remove_file(target)
+        .map_err(|e| format!("failed to remove existing file {:?}: {}", target, e))?;
+    Ok(())
+}
+
+/// apply `files` for `mode`, all-or-nothing: if any file fails partway
+/// through, every target created so far in this call is rolled back
+/// (removed) before the error is returned, so a failed `setup` never leaves
+/// a half-applied profile behind.
+fn create_symlinks(
+    files: &Vec<String>,
+    source_dir: &str,
+    mode: Mode,
+    backup_dir: &str,
+) -> Result<(String, HashMap<String, String>), String> {
     let source_path = expand_home(source_dir.trim_end_matches('/'));
+    let mut hashes = HashMap::new();
+    let mut created: Vec<PathBuf> = Vec::new();
+
+    let result = (|| -> Result<(), String> {
+        for file in files {
+            let target = expand_home(file);
+            let home_relative = file.strip_prefix('~').unwrap_or(file).trim_start_matches('/');
+            let source = source_path.join(home_relative);
+
+            if let Some(parent) = target.parent()
+                && !parent.exists()
+            {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("failed to create directory {:?}: {}", parent, e))?;
+            }
 
-    for file in files {
-        let target = PathBuf::from(file);
-        let source = source_path.join(&target);
+            guard_against_clobber(&target, file, backup_dir)?;
 
-        if let Some(parent) = target.parent()
-            && !parent.exists()
-        {
-            fs::create_dir_all(parent)
-                .map_err(|e| format!("failed to create directory {:?}: {}", parent, e))?;
-        }
+            match mode {
+                Mode::Linked => {
+                    if target.is_symlink() {
+                        fs::remove_file(&target).map_err(|e| {
+                            format!("failed to replace existing symlink {:?}: {}", target, e)
+                        })?;
+                    }
+                    std::os::unix::fs::symlink(&source, &target).map_err(|e| {
+                        format!(
+                            "failed to create symlink {:?} -> {:?}: {}",
+                            source, target, e
+                        )
+                    })?;
+                    info!("created symlink: {:?} -> {:?}", target, source);
+                }
+                Mode::Copied => {
+                    if target.is_symlink() || target.exists() {
+                        fs::remove_file(&target).map_err(|e| {
+                            format!("failed to replace existing file {:?}: {}", target, e)
+                        })?;
+                    }
+                    let hash = copy_file(&source, &target)?;
+                    info!("copied {:?} -> {:?}", source, target);
+                    hashes.insert(file.clone(), hash);
+                }
+            }
 
-        std::os::unix::fs::symlink(&source, &target).map_err(|e| {
-            format!(
-                "failed to create symlink {:?} -> {:?}: {}",
-                source, target, e
-            )
-        })?;
+            created.push(target);
+        }
+        Ok(())
+    })();
 
-        info!("created symlink: {:?} -> {:?}", target, source);
+    if let Err(e) = result {
+        for target in created.iter().rev() {
+            // only remove what we just created: a symlink in Linked mode,
+            // or the file we just wrote in Copied mode. if it's already
+            // gone (or was swapped out from under us), leave it alone.
+            let should_remove = match mode {
+                Mode::Linked => target.is_symlink(),
+                Mode::Copied => target.exists(),
+            };
+            if should_remove {
+                if let Err(rollback_err) = fs::remove_file(target) {
+                    eprintln!(
+                        "error: failed to roll back {:?} after setup failure: {}",
+                        target, rollback_err
+                    );
+                }
+            }
+        }
+        return Err(e);
     }
-    Ok(chrono::Local::now()
+
+    let created_at = chrono::Local::now()
         .format("%Y-%m-%d--%H-%M-%S")
-        .to_string())
+        .to_string();
+    Ok((created_at, hashes))
 }
 
-fn create_backup(files: &Vec<String>, target_dir: &str) -> Result<PathBuf, String> {
+/// archive `files` into a timestamped zip under `target_dir`, without
+/// touching the originals. each entry is stored under its profile-relative
+/// path (e.g. `.bashrc`), which is also how `restore` knows where to put it
+/// back.
+fn archive_files(files: &Vec<String>, target_dir: &str) -> Result<PathBuf, String> {
     let backup_path = expand_home(target_dir);
 
     fs::create_dir_all(&backup_path)
@@ -413,7 +704,7 @@ fn create_backup(files: &Vec<String>, target_dir: &str) -> Result<PathBuf, Strin
     let options = zip::write::FileOptions::<()>::default();
 
     for file in files {
-        let source = PathBuf::from(file);
+        let source = expand_home(file);
 
         if source.exists() {
             let content = fs::read(&source)
@@ -423,10 +714,7 @@ fn create_backup(files: &Vec<String>, target_dir: &str) -> Result<PathBuf, Strin
                 .map_err(|e| format!("failed to add file to zip: {}", e))?;
             zip.write_all(&content)
                 .map_err(|e| format!("failed to write to zip: {}", e))?;
-            info!("backed up to zip: {}", file);
-
-            fs::remove_file(file)
-                .map_err(|e| format!("failed to remove file from {:?}: {}", file, e))?;
+            info!("archived to zip: {}", file);
         } else {
             debug!("skipping backup for non-existent file: {:?}", source);
         }
@@ -434,33 +722,65 @@ fn create_backup(files: &Vec<String>, target_dir: &str) -> Result<PathBuf, Strin
     zip.finish()
         .map_err(|e| format!("failed to finalize zip: {}", e))?;
 
-    info!("backup created at {:?}", backup_path);
+    info!("backup created at {:?}", zip_path);
+    Ok(zip_path)
+}
+
+/// archive `files`, then remove the originals, as `setup` needs before it
+/// lays down symlinks/copies over top of them.
+fn create_backup(files: &Vec<String>, target_dir: &str) -> Result<PathBuf, String> {
+    let zip_path = archive_files(files, target_dir)?;
+
+    for file in files {
+        let source = expand_home(file);
+        if source.exists() {
+            fs::remove_file(&source)
+                .map_err(|e| format!("failed to remove file {:?}: {}", source, e))?;
+        }
+    }
+
     Ok(zip_path)
 }
 
-fn setup(profile: String) -> Result<(), String> {
+fn setup(profile: String, mode: Option<Mode>) -> Result<(), String> {
     let mut state = State::new().map_err(|e| format!("failed to read state: {}", e))?;
+
+    if !state.profiles.contains_key(&profile) {
+        let mut available: Vec<&str> = state.profiles.keys().map(String::as_str).collect();
+        available.sort();
+        return Err(format!(
+            "profile '{}' not found. available profiles: {}",
+            profile,
+            available.join(", ")
+        ));
+    }
+
     state = state.set_active_profile(&profile);
-    info!("profile set to {}", profile);
+    if let Some(mode) = mode {
+        state.mode = mode;
+    }
+    info!("profile set to {} ({} mode)", profile, state.mode);
 
     let profile_files = state
         .profiles
         .get(&profile)
-        .or_else(|| {
-            info!("profile {} not found! trying 'default' profile", profile);
-            state.profiles.get("default")
-        })
-        .ok_or("no 'default' profile found!")?;
+        .ok_or_else(|| format!("profile '{}' not found", profile))?;
 
-    let backup_dir = create_backup(profile_files, &state.backup_path)?;
-    info!("backup completed at {:?}", backup_dir);
+    let backup_zip = create_backup(profile_files, &state.backup_path)?;
+    info!("backup completed at {:?}", backup_zip);
 
-    let created_at = create_symlinks(profile_files, &state.path)?;
+    let (created_at, hashes) = create_symlinks(
+        profile_files,
+        &state.path,
+        state.mode,
+        &state.backup_path,
+    )?;
 
     let history = History {
         created_at,
-        backup: backup_dir.display().to_string(),
+        backup: backup_zip.display().to_string(),
         files: profile_files.clone(),
+        hashes: (!hashes.is_empty()).then_some(hashes),
     };
     state = state.append_history(history);
     state
@@ -470,6 +790,31 @@ fn setup(profile: String) -> Result<(), String> {
     Ok(())
 }
 
+fn refresh() -> Result<(), String> {
+    let state = State::new().map_err(|e| format!("failed to read state: {}", e))?;
+    let repo_path = expand_home(&state.path);
+
+    match state.source_type.unwrap_or(SourceType::GitHttps) {
+        SourceType::Zip => {
+            info!("refreshing zip source by re-downloading and re-extracting");
+            source::ZipFetcher.fetch(&state.url, "", &repo_path)?;
+        }
+        SourceType::GitHttps | SourceType::GitSsh => {
+            let branch = state.branch.as_deref().unwrap_or("");
+            refresh_git_repo(&state.url, branch, &repo_path)?;
+        }
+    }
+
+    if let Some(profile) = &state.active_profile {
+        if let Some(files) = state.profiles.get(profile) {
+            create_symlinks(files, &state.path, state.mode, &state.backup_path)?;
+            info!("re-validated symlinks for profile {}", profile);
+        }
+    }
+
+    Ok(())
+}
+
 fn status() -> Result<(), String> {
     let state = State::new().map_err(|e| format!("failed to read state: {}", e))?;
 
@@ -479,12 +824,36 @@ fn status() -> Result<(), String> {
             println!();
 
             if let Some(files) = state.profiles.get(profile) {
-                println!("synced dotfiles ({}):", files.len());
+                println!("synced dotfiles ({}, {} mode):", files.len(), state.mode);
+
+                let recorded_hashes = state
+                    .history
+                    .as_ref()
+                    .and_then(|history| history.iter().rev().find_map(|h| h.hashes.as_ref()));
+
                 for file in files {
-                    let status = if expand_home(file).is_symlink() {
-                        "+"
-                    } else {
-                        "-"
+                    let status = match state.mode {
+                        Mode::Linked => {
+                            if expand_home(file).is_symlink() {
+                                "+"
+                            } else {
+                                "-"
+                            }
+                        }
+                        Mode::Copied => {
+                            let target = expand_home(file);
+                            if !target.exists() {
+                                "-"
+                            } else {
+                                let current_hash = hash_file(&target).ok();
+                                let recorded_hash = recorded_hashes.and_then(|h| h.get(file));
+                                if current_hash.as_deref() == recorded_hash.map(String::as_str) {
+                                    "+"
+                                } else {
+                                    "modified"
+                                }
+                            }
+                        }
                     };
                     println!("  {} {}", status, file);
                 }
@@ -501,16 +870,123 @@ fn status() -> Result<(), String> {
     Ok(())
 }
 
-fn destroy() -> Result<(), String> {
+fn backup() -> Result<(), String> {
+    let mut state = State::new().map_err(|e| format!("failed to read state: {}", e))?;
+    let profile = state
+        .active_profile
+        .clone()
+        .ok_or("no active profile set. run 'dotsync setup --profile=<name>' first.")?;
+    let files = state
+        .profiles
+        .get(&profile)
+        .ok_or_else(|| format!("no files found for profile '{}'", profile))?
+        .clone();
+
+    let zip_path = archive_files(&files, &state.backup_path)?;
+
+    let history = History {
+        created_at: chrono::Local::now()
+            .format("%Y-%m-%d--%H-%M-%S")
+            .to_string(),
+        backup: zip_path.display().to_string(),
+        files,
+        hashes: None,
+    };
+    state = state.append_history(history);
+    state
+        .write_state_file()
+        .map_err(|e| format!("failed to write state file: {}", e))?;
+
+    Ok(())
+}
+
+fn restore(archive: Option<PathBuf>, force: bool) -> Result<(), String> {
+    let state = State::new().map_err(|e| format!("failed to read state: {}", e))?;
+
+    let archive_path = match archive {
+        Some(path) => path,
+        None => {
+            let last = state
+                .history
+                .as_ref()
+                .and_then(|history| history.last())
+                .ok_or("no archive given and no backup recorded in state history")?;
+            PathBuf::from(&last.backup)
+        }
+    };
+
+    let zip_file = fs::File::open(&archive_path)
+        .map_err(|e| format!("failed to open backup archive {:?}: {}", archive_path, e))?;
+    let mut zip = zip::ZipArchive::new(zip_file)
+        .map_err(|e| format!("failed to read backup archive {:?}: {}", archive_path, e))?;
+
+    for i in 0..zip.len() {
+        let mut entry = zip
+            .by_index(i)
+            .map_err(|e| format!("failed to read zip entry {}: {}", i, e))?;
+        let stored_path = entry.name().to_string();
+        let enclosed_name = match entry.enclosed_name() {
+            Some(name) => name,
+            None => {
+                return Err(format!(
+                    "zip entry {:?} attempts to escape the extraction directory",
+                    stored_path
+                ));
+            }
+        };
+        let target = expand_home(&enclosed_name.display().to_string());
+
+        if target.is_symlink() && !force {
+            return Err(format!(
+                "refusing to overwrite existing symlink {:?} (use --force)",
+                target
+            ));
+        }
+
+        if target.is_symlink() {
+            // replace the symlink itself rather than writing through it into
+            // the repository file it points at.
+            fs::remove_file(&target)
+                .map_err(|e| format!("failed to remove existing symlink {:?}: {}", target, e))?;
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create directory {:?}: {}", parent, e))?;
+        }
+
+        let mut out_file = fs::File::create(&target)
+            .map_err(|e| format!("failed to create file {:?}: {}", target, e))?;
+        std::io::copy(&mut entry, &mut out_file)
+            .map_err(|e| format!("failed to write file {:?}: {}", target, e))?;
+
+        info!("restored {:?} from {}", target, stored_path);
+    }
+
+    info!("restore complete from {:?}", archive_path);
+    Ok(())
+}
+
+fn destroy(force: bool) -> Result<(), String> {
     let state = State::new().map_err(|e| format!("failed to read state: {}", e))?;
     match &state.active_profile {
         Some(profile) => {
             println!("active profile: {}", profile);
             if let Some(files) = state.profiles.get(profile) {
-                for file in files {
-                    match std::fs::remove_file(expand_home(file)) {
-                        Ok(_) => println!("removed {}", file),
-                        Err(e) => eprintln!("couldn't remove symlink for file {}: {:?}", file, e),
+                if state.mode == Mode::Copied && !force {
+                    println!(
+                        "profile is in Copied mode: {} would delete your actual dotfiles, \
+                         not just symlinks. pass --force to remove them anyway.",
+                        files.len()
+                    );
+                } else {
+                    for file in files {
+                        match std::fs::remove_file(expand_home(file)) {
+                            Ok(_) => println!("removed {}", file),
+                            Err(e) => {
+                                eprintln!("couldn't remove symlink for file {}: {:?}", file, e)
+                            }
+                        }
                     }
                 }
             } else {
@@ -527,6 +1003,193 @@ fn destroy() -> Result<(), String> {
     Ok(())
 }
 
+/// debounce window: events for the same path within this window are
+/// collapsed into one, since editors commonly emit several write/rename
+/// events per save.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// map from each live dotfile's watched absolute path back to its
+/// profile-relative entry, so filesystem events can be mapped to the
+/// action that should run.
+fn build_watch_map(state: &State) -> HashMap<PathBuf, String> {
+    let mut map = HashMap::new();
+    if let Some(profile) = &state.active_profile {
+        if let Some(files) = state.profiles.get(profile) {
+            for file in files {
+                map.insert(expand_home(file), file.clone());
+            }
+        }
+    }
+    map
+}
+
+/// copy a locally-edited dotfile back into the repo (in `Mode::Copied`; a
+/// Linked-mode symlink already points into the repo) and commit/push it, so
+/// edits made directly on a machine propagate back out.
+fn push_live_change(repo_path: &PathBuf, state: &State, entry: &str) -> Result<(), String> {
+    let target = expand_home(entry);
+    let home_relative = entry.strip_prefix('~').unwrap_or(entry).trim_start_matches('/');
+    let source = repo_path.join(home_relative);
+
+    // in Linked mode `target` is a symlink to `source`, so the edit is already
+    // in the repo; copying the file onto itself would truncate it.
+    if state.mode == Mode::Copied {
+        fs::copy(&target, &source).map_err(|e| {
+            format!("failed to copy {:?} into repo at {:?}: {}", target, source, e)
+        })?;
+    }
+
+    let add = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .arg("add")
+        .arg(home_relative)
+        .output()
+        .map_err(|e| format!("failed to execute git add: {}", e))?;
+    if !add.status.success() {
+        return Err(format!(
+            "git add failed: {}",
+            String::from_utf8_lossy(&add.stderr)
+        ));
+    }
+
+    let commit = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .arg("commit")
+        .arg("-m")
+        .arg(format!("dotsync: update {}", entry))
+        .output()
+        .map_err(|e| format!("failed to execute git commit: {}", e))?;
+    if !commit.status.success() {
+        let stderr = String::from_utf8_lossy(&commit.stderr);
+        if stderr.contains("nothing to commit") {
+            return Ok(());
+        }
+        return Err(format!("git commit failed: {}", stderr));
+    }
+
+    let mut push_cmd = Command::new("git");
+    push_cmd.arg("-C").arg(repo_path).arg("push").arg("origin");
+    if let Some(branch) = state.branch.as_deref().filter(|b| !b.is_empty()) {
+        push_cmd.arg(branch);
+    }
+    let push = push_cmd
+        .output()
+        .map_err(|e| format!("failed to execute git push: {}", e))?;
+    if !push.status.success() {
+        return Err(format!(
+            "git push failed: {}",
+            String::from_utf8_lossy(&push.stderr)
+        ));
+    }
+
+    info!("pushed update for {} back to repository", entry);
+    Ok(())
+}
+
+fn watch(push: bool, interval: Option<u64>) -> Result<(), String> {
+    let mut state = State::new().map_err(|e| format!("failed to read state: {}", e))?;
+    let state_file_path = expand_home(State::STATE_FILE_PATH);
+    let repo_path = expand_home(&state.path);
+    let mut watched_files = build_watch_map(&state);
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })
+    .map_err(|e| format!("failed to create filesystem watcher: {}", e))?;
+
+    watcher
+        .watch(&repo_path, RecursiveMode::Recursive)
+        .map_err(|e| format!("failed to watch repository at {:?}: {}", repo_path, e))?;
+    watcher
+        .watch(&state_file_path, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("failed to watch state file: {}", e))?;
+    for target in watched_files.keys() {
+        if let Err(e) = watcher.watch(target, RecursiveMode::NonRecursive) {
+            debug!("skipping watch for {:?}: {}", target, e);
+        }
+    }
+
+    info!(
+        "watching {:?} and {} live dotfile(s){}",
+        repo_path,
+        watched_files.len(),
+        interval
+            .map(|secs| format!(", pulling every {}s", secs))
+            .unwrap_or_default()
+    );
+
+    let interval_duration = interval.map(Duration::from_secs);
+    let mut last_pull = Instant::now();
+    let mut last_handled: HashMap<PathBuf, Instant> = HashMap::new();
+
+    loop {
+        match rx.recv_timeout(Duration::from_millis(500)) {
+            Ok(Ok(event)) => {
+                for path in event.paths {
+                    let now = Instant::now();
+                    if let Some(last) = last_handled.get(&path) {
+                        if now.duration_since(*last) < WATCH_DEBOUNCE {
+                            continue;
+                        }
+                    }
+                    last_handled.insert(path.clone(), now);
+
+                    if path == state_file_path {
+                        info!("state file changed on disk, reloading");
+                        state =
+                            State::new().map_err(|e| format!("failed to reload state: {}", e))?;
+                        watched_files = build_watch_map(&state);
+                        for target in watched_files.keys() {
+                            let _ = watcher.watch(target, RecursiveMode::NonRecursive);
+                        }
+                        continue;
+                    }
+
+                    if let Some(entry) = watched_files.get(&path) {
+                        info!("live dotfile changed: {}", entry);
+                        if push {
+                            if let Err(e) = push_live_change(&repo_path, &state, entry) {
+                                eprintln!("error: failed to push change for {}: {}", entry, e);
+                            }
+                        }
+                        continue;
+                    }
+
+                    if path.starts_with(&repo_path) {
+                        if let Some(profile) = &state.active_profile {
+                            if let Some(files) = state.profiles.get(profile) {
+                                if let Err(e) =
+                                    create_symlinks(files, &state.path, state.mode, &state.backup_path)
+                                {
+                                    eprintln!("error: failed to re-validate symlinks: {}", e);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(Err(e)) => eprintln!("watch error: {}", e),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Some(duration) = interval_duration {
+                    if last_pull.elapsed() >= duration {
+                        info!("periodic refresh interval elapsed, pulling");
+                        if let Err(e) = refresh() {
+                            eprintln!("error: periodic refresh failed: {}", e);
+                        }
+                        last_pull = Instant::now();
+                    }
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err("filesystem watcher channel disconnected".to_string());
+            }
+        }
+    }
+}
+
 fn main() {
     let env = Env::default().filter_or("LOG_LEVEL", "info");
     env_logger::init_from_env(env);
@@ -534,14 +1197,13 @@ fn main() {
 
     let result: Result<(), String> = match cli.command {
         Commands::Init { config } => init(config),
-        Commands::Setup { profile, .. } => setup(profile),
+        Commands::Setup { profile, mode, .. } => setup(profile, mode),
         Commands::Status {} => status(),
-        Commands::Destroy {} => destroy(),
-        Commands::Refresh {} => Ok(()),
-        Commands::Backup {} => {
-            info!("Backup command");
-            Ok(())
-        }
+        Commands::Destroy { force } => destroy(force),
+        Commands::Refresh {} => refresh(),
+        Commands::Watch { push, interval } => watch(push, interval),
+        Commands::Backup {} => backup(),
+        Commands::Restore { archive, force } => restore(archive, force),
     };
 
     if let Err(e) = result {
@@ -549,3 +1211,55 @@ fn main() {
         std::process::exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `refresh` (and `watch`'s repo-change handler) re-run `create_symlinks`
+    /// against a profile that `setup` already applied, so a second Linked-mode
+    /// pass over the same files must succeed rather than failing with EEXIST.
+    #[test]
+    fn create_symlinks_linked_mode_is_idempotent() {
+        let base = std::env::temp_dir().join(format!(
+            "dotsync-test-{}-{}",
+            std::process::id(),
+            line!()
+        ));
+        let home = base.join("home");
+        let repo = base.join("repo");
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(repo.join(".testrc"), b"hello").unwrap();
+
+        let original_home = std::env::var("HOME").ok();
+        unsafe {
+            std::env::set_var("HOME", &home);
+        }
+
+        let files = vec!["~/.testrc".to_string()];
+        let backup_dir = home.join("backups").display().to_string();
+
+        let first = create_symlinks(&files, &repo.display().to_string(), Mode::Linked, &backup_dir);
+        assert!(first.is_ok(), "initial setup should succeed: {:?}", first);
+        assert_eq!(
+            fs::read_to_string(home.join(".testrc")).expect("symlink should resolve to repo file"),
+            "hello",
+            "symlink must point at the real file in the repo, not a dangling `<repo>/~/...` path",
+        );
+
+        let second = create_symlinks(&files, &repo.display().to_string(), Mode::Linked, &backup_dir);
+        assert!(
+            second.is_ok(),
+            "re-running create_symlinks (what refresh/watch do) against an \
+             already-setup profile should succeed, not EEXIST: {:?}",
+            second
+        );
+
+        match original_home {
+            Some(value) => unsafe { std::env::set_var("HOME", value) },
+            None => unsafe { std::env::remove_var("HOME") },
+        }
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,184 @@
+//! Native fetch backends for the sources dotsync pulls dotfiles from.
+//!
+//! `init` used to shell out to `git`, `curl`, and `unzip` to fetch a source,
+//! which only gives us stderr strings to work with and requires those binaries on
+//! `PATH`. `GitFetcher` drives `git2` directly instead, which means `GitSsh`
+//! sources get a real credential callback (ssh-agent, falling back to
+//! libgit2's default credentials) rather than hoping the system git is
+//! configured for it, and `ZipFetcher` streams the archive through an HTTP
+//! client and the `zip` crate, rejecting any entry that tries to escape the
+//! destination directory via `..`.
+//!
+//! If a native git clone fails and a system `git` binary is available, we fall
+//! back to shelling out to it, so a machine where the native backend can't
+//! do something (e.g. no ssh-agent wired up) isn't left completely stuck.
+//! `ZipFetcher` has no such fallback. Pulling an existing checkout (`refresh`)
+//! is not done here and runs the system `git`.
+
+use log::{debug, info};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+
+use crate::{git_is_installed, SourceType};
+
+pub trait Fetcher {
+    fn fetch(&self, url: &str, branch: &str, dest: &Path) -> Result<(), String>;
+}
+
+pub fn fetcher_for(source_type: SourceType) -> Box<dyn Fetcher> {
+    match source_type {
+        SourceType::GitHttps | SourceType::GitSsh => Box::new(GitFetcher),
+        SourceType::Zip => Box::new(ZipFetcher),
+    }
+}
+
+pub struct GitFetcher;
+
+impl Fetcher for GitFetcher {
+    fn fetch(&self, url: &str, branch: &str, dest: &Path) -> Result<(), String> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create directories: {}", e))?;
+        }
+
+        match native_clone(url, branch, dest) {
+            Ok(()) => {
+                info!("successfully cloned repository to {:?}", dest);
+                Ok(())
+            }
+            Err(native_err) => {
+                if git_is_installed() {
+                    debug!(
+                        "native git clone failed ({}), falling back to system git",
+                        native_err
+                    );
+                    shell_clone(url, branch, dest)
+                } else {
+                    Err(native_err)
+                }
+            }
+        }
+    }
+}
+
+fn native_clone(url: &str, branch: &str, dest: &Path) -> Result<(), String> {
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(|_url, username_from_url, allowed_types| {
+        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
+            if let Some(username) = username_from_url {
+                if let Ok(cred) = git2::Cred::ssh_key_from_agent(username) {
+                    return Ok(cred);
+                }
+            }
+        }
+        git2::Cred::default()
+    });
+
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+
+    let mut builder = git2::build::RepoBuilder::new();
+    builder.fetch_options(fetch_options);
+    if !branch.is_empty() {
+        builder.branch(branch);
+    }
+
+    builder
+        .clone(url, dest)
+        .map(|_repo| ())
+        .map_err(|e| format!("native git clone failed: {}", e))
+}
+
+fn shell_clone(url: &str, branch: &str, dest: &Path) -> Result<(), String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("clone");
+
+    if !branch.is_empty() {
+        cmd.arg("--branch").arg(branch);
+    }
+
+    cmd.arg(url).arg(dest);
+
+    let output = cmd
+        .output()
+        .map_err(|e| format!("failed to execute git: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "git clone failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
+
+pub struct ZipFetcher;
+
+impl Fetcher for ZipFetcher {
+    fn fetch(&self, url: &str, _branch: &str, dest: &Path) -> Result<(), String> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create directories: {}", e))?;
+        }
+
+        info!("downloading zip from {}", url);
+        let bytes = ureq::get(url)
+            .call()
+            .map_err(|e| format!("failed to download {}: {}", url, e))?
+            .into_reader()
+            .bytes()
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|e| format!("failed to read response body from {}: {}", url, e))?;
+
+        extract_zip(&bytes, dest)?;
+        info!("successfully extracted zip to {:?}", dest);
+        Ok(())
+    }
+}
+
+fn extract_zip(bytes: &[u8], dest: &Path) -> Result<(), String> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .map_err(|e| format!("failed to read zip archive: {}", e))?;
+
+    fs::create_dir_all(dest)
+        .map_err(|e| format!("failed to create directory {:?}: {}", dest, e))?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("failed to read zip entry {}: {}", i, e))?;
+
+        let name = match entry.enclosed_name() {
+            Some(name) => name,
+            None => {
+                return Err(format!(
+                    "zip entry {:?} attempts to escape the extraction directory",
+                    entry.name()
+                ));
+            }
+        };
+
+        let out_path = dest.join(name);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path)
+                .map_err(|e| format!("failed to create directory {:?}: {}", out_path, e))?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create directory {:?}: {}", parent, e))?;
+        }
+
+        let mut out_file = fs::File::create(&out_path)
+            .map_err(|e| format!("failed to create file {:?}: {}", out_path, e))?;
+        std::io::copy(&mut entry, &mut out_file)
+            .map_err(|e| format!("failed to write file {:?}: {}", out_path, e))?;
+    }
+
+    Ok(())
+}